@@ -0,0 +1,106 @@
+//! The thread-local blocking primitive used by channels and `select!`.
+//!
+//! Every thread that might park on a channel operation owns an `Actor`. Parking and waking are
+//! plain condvar operations; the only twist is that a wakeup also records *which* `CaseId` caused
+//! it, so code parked inside `select!` can tell which of its registered operations fired.
+
+use std::sync::{Arc, Mutex};
+use std::sync::Condvar;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::SeqCst;
+use std::time::Instant;
+
+use CaseId;
+
+struct Inner {
+    /// Set to `true` as soon as this actor has been woken up.
+    woken: AtomicBool,
+    /// The case that woke us up, or `CaseId::none()` if we simply timed out.
+    selected: Mutex<CaseId>,
+    lock: Mutex<()>,
+    cond: Condvar,
+}
+
+/// A thread parked on one or more channel operations.
+pub struct Actor {
+    inner: Inner,
+}
+
+impl Actor {
+    fn new() -> Self {
+        Actor {
+            inner: Inner {
+                woken: AtomicBool::new(false),
+                selected: Mutex::new(CaseId::none()),
+                lock: Mutex::new(()),
+                cond: Condvar::new(),
+            },
+        }
+    }
+
+    /// Clears this actor's wakeup state before a new attempt to block.
+    pub fn reset(&self) {
+        self.inner.woken.store(false, SeqCst);
+        *self.inner.selected.lock().unwrap() = CaseId::none();
+    }
+
+    /// Blocks until woken up or `deadline` passes, returning whether we were woken up.
+    pub fn wait_until(&self, deadline: Option<Instant>) -> bool {
+        let mut guard = self.inner.lock.lock().unwrap();
+        while !self.inner.woken.load(SeqCst) {
+            match deadline {
+                None => guard = self.inner.cond.wait(guard).unwrap(),
+                Some(d) => {
+                    let now = Instant::now();
+                    if now >= d {
+                        return false;
+                    }
+                    let (g, res) = self.inner.cond.wait_timeout(guard, d - now).unwrap();
+                    guard = g;
+                    if res.timed_out() {
+                        return self.inner.woken.load(SeqCst);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Wakes up this actor, recording `case_id` as the operation that fired (first wakeup wins).
+    pub fn wake(&self, case_id: CaseId) {
+        let _guard = self.inner.lock.lock().unwrap();
+        if !self.inner.woken.swap(true, SeqCst) {
+            *self.inner.selected.lock().unwrap() = case_id;
+        }
+        self.inner.cond.notify_one();
+    }
+
+    /// Returns the case that woke this actor up, if any.
+    pub fn selected(&self) -> CaseId {
+        *self.inner.selected.lock().unwrap()
+    }
+}
+
+thread_local! {
+    static ACTOR: Arc<Actor> = Arc::new(Actor::new());
+}
+
+/// Returns a handle to the current thread's actor.
+pub fn current() -> Arc<Actor> {
+    ACTOR.with(|a| a.clone())
+}
+
+/// Clears the current thread's actor before it attempts to block again.
+pub fn current_reset() {
+    ACTOR.with(|a| a.reset())
+}
+
+/// Blocks the current thread until its actor is woken up or `deadline` passes.
+pub fn current_wait_until(deadline: Option<Instant>) -> bool {
+    ACTOR.with(|a| a.wait_until(deadline))
+}
+
+/// Returns the case that last woke up the current thread's actor.
+pub fn current_selected() -> CaseId {
+    ACTOR.with(|a| a.selected())
+}
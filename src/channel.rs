@@ -0,0 +1,504 @@
+//! The public `Sender`/`Receiver` handles and the channel constructors.
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use CaseId;
+use err::{RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError};
+use flavors::{array, list, zero};
+use monitor::Monitor;
+
+/// The channel flavor backing a `Sender`/`Receiver` pair.
+pub(crate) enum Flavor<T> {
+    /// An unbounded, linked-list-backed channel.
+    List(list::Channel<T>),
+    /// A bounded, array-backed channel.
+    Array(array::Channel<T>),
+    /// A zero-capacity rendezvous channel.
+    Zero(zero::Channel<T>),
+}
+
+impl<T> Flavor<T> {
+    fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        match *self {
+            Flavor::List(ref chan) => chan.try_send(value),
+            Flavor::Array(ref chan) => chan.try_send(value),
+            Flavor::Zero(ref chan) => chan.try_send(value),
+        }
+    }
+
+    fn send(&self, value: T, deadline: Option<Instant>, case_id: CaseId) -> Result<(), SendTimeoutError<T>> {
+        match *self {
+            Flavor::List(ref chan) => chan.send(value),
+            Flavor::Array(ref chan) => chan.send(value, deadline, case_id),
+            Flavor::Zero(ref chan) => chan.send(value, deadline, case_id),
+        }
+    }
+
+    fn try_recv(&self) -> Result<T, TryRecvError> {
+        match *self {
+            Flavor::List(ref chan) => chan.try_recv(),
+            Flavor::Array(ref chan) => chan.try_recv(),
+            Flavor::Zero(ref chan) => chan.try_recv(),
+        }
+    }
+
+    fn recv_until(&self, deadline: Option<Instant>, case_id: CaseId) -> Result<T, RecvTimeoutError> {
+        match *self {
+            Flavor::List(ref chan) => chan.recv_until(deadline, case_id),
+            Flavor::Array(ref chan) => chan.recv_until(deadline, case_id),
+            Flavor::Zero(ref chan) => chan.recv_until(deadline, case_id),
+        }
+    }
+
+    fn close(&self) -> bool {
+        match *self {
+            Flavor::List(ref chan) => chan.close(),
+            Flavor::Array(ref chan) => chan.close(),
+            Flavor::Zero(ref chan) => chan.close(),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        match *self {
+            Flavor::List(ref chan) => chan.is_closed(),
+            Flavor::Array(ref chan) => chan.is_closed(),
+            Flavor::Zero(ref chan) => chan.is_closed(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match *self {
+            Flavor::List(ref chan) => chan.len(),
+            Flavor::Array(ref chan) => chan.len(),
+            Flavor::Zero(ref chan) => chan.len(),
+        }
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        match *self {
+            Flavor::List(_) => None,
+            Flavor::Array(ref chan) => Some(chan.capacity()),
+            Flavor::Zero(_) => Some(0),
+        }
+    }
+
+    /// Returns `true` if a `try_send` would currently fail with `Full`: for a list channel that's
+    /// never, for an array channel that's "at capacity", and for a rendezvous channel that's "no
+    /// receiver is parked to take the value right now".
+    fn is_full(&self) -> bool {
+        match *self {
+            Flavor::List(_) => false,
+            Flavor::Array(ref chan) => chan.len() >= chan.capacity(),
+            Flavor::Zero(ref chan) => !chan.has_waiting_receiver(),
+        }
+    }
+
+    fn receivers(&self) -> &Monitor {
+        match *self {
+            Flavor::List(ref chan) => chan.receivers(),
+            Flavor::Array(ref chan) => chan.receivers(),
+            Flavor::Zero(ref chan) => chan.receivers(),
+        }
+    }
+
+    fn senders(&self) -> Option<&Monitor> {
+        match *self {
+            Flavor::List(_) => None,
+            Flavor::Array(ref chan) => Some(chan.senders()),
+            Flavor::Zero(ref chan) => Some(chan.senders()),
+        }
+    }
+}
+
+struct Channel<T> {
+    flavor: Flavor<T>,
+    sender_count: AtomicUsize,
+    receiver_count: AtomicUsize,
+}
+
+/// Creates an unbounded channel, returning a `(Sender<T>, Receiver<T>)` pair.
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    let chan = Arc::new(Channel {
+        flavor: Flavor::List(list::Channel::new()),
+        sender_count: AtomicUsize::new(1),
+        receiver_count: AtomicUsize::new(1),
+    });
+    (Sender { inner: chan.clone() }, Receiver { inner: chan })
+}
+
+/// Creates a channel that can hold at most `cap` messages before `send` blocks.
+///
+/// A capacity of `0` creates a rendezvous channel, where a `send` only completes once a `recv`
+/// is there to receive it, and vice versa.
+pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let flavor = if cap == 0 {
+        Flavor::Zero(zero::Channel::new())
+    } else {
+        Flavor::Array(array::Channel::with_capacity(cap))
+    };
+    let chan = Arc::new(Channel {
+        flavor,
+        sender_count: AtomicUsize::new(1),
+        receiver_count: AtomicUsize::new(1),
+    });
+    (Sender { inner: chan.clone() }, Receiver { inner: chan })
+}
+
+/// The sending half of a channel.
+pub struct Sender<T> {
+    inner: Arc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends a message, blocking until the channel can accept it.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.inner
+            .flavor
+            .send(value, None, CaseId::new())
+            .map_err(|err| match err {
+                SendTimeoutError::Disconnected(v) => SendError(v),
+                SendTimeoutError::Timeout(v) => SendError(v),
+            })
+    }
+
+    /// Sends a message, giving up if the channel isn't ready to accept it within `timeout`.
+    pub fn send_timeout(&self, value: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.inner
+            .flavor
+            .send(value, Some(Instant::now() + timeout), CaseId::new())
+    }
+
+    /// Sends a message without blocking.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.inner.flavor.try_send(value)
+    }
+
+    /// Returns this channel's capacity, or `None` if it is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.inner.flavor.capacity()
+    }
+
+    /// Returns `true` if the channel is closed, either because all receivers have disconnected or
+    /// because it was closed explicitly with `Receiver::close`.
+    pub fn is_closed(&self) -> bool {
+        self.inner.flavor.is_closed()
+    }
+
+    /// Polls for capacity to send a message without blocking, registering `cx`'s waker if the
+    /// channel isn't currently ready to accept one.
+    ///
+    /// Once this returns `Poll::Ready(())`, the caller should perform the send with `try_send`.
+    /// Unbounded channels are always reported ready, since there is no fixed capacity for this to
+    /// poll on. Rendezvous channels are ready only while a receiver is parked waiting to take the
+    /// value directly; otherwise this parks until one arrives, rather than reporting `Ready` and
+    /// leaving the caller to busy-loop against `try_send`.
+    pub fn poll_send(&self, cx: &mut Context) -> Poll<()> {
+        if !self.inner.flavor.is_full() || self.is_closed() {
+            return Poll::Ready(());
+        }
+
+        if let Some(senders) = self.inner.flavor.senders() {
+            senders.register_waker(cx.waker().clone());
+        }
+
+        // Re-check after registering, in case the channel became ready in the meantime.
+        if self.inner.flavor.is_full() && !self.is_closed() {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.inner.sender_count.fetch_add(1, SeqCst);
+        Sender { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        // The last sender closes the channel so receivers drain what's left, then observe
+        // `Disconnected` instead of blocking forever.
+        if self.inner.sender_count.fetch_sub(1, SeqCst) == 1 {
+            self.inner.flavor.close();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+/// The receiving half of a channel.
+pub struct Receiver<T> {
+    inner: Arc<Channel<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Blocks until a message is received or the channel is disconnected.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.inner
+            .flavor
+            .recv_until(None, CaseId::new())
+            .map_err(|_| RecvError)
+    }
+
+    /// Waits for a message until `timeout` elapses.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.inner
+            .flavor
+            .recv_until(Some(Instant::now() + timeout), CaseId::new())
+    }
+
+    /// Receives a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.inner.flavor.try_recv()
+    }
+
+    /// Closes the channel, waking up every blocked sender and receiver.
+    pub fn close(&self) -> bool {
+        self.inner.flavor.close()
+    }
+
+    /// Returns `true` if the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.flavor.is_closed()
+    }
+
+    /// Returns the number of messages currently buffered.
+    pub fn len(&self) -> usize {
+        self.inner.flavor.len()
+    }
+
+    /// Polls for a message without blocking, registering `cx`'s waker if the channel is
+    /// currently empty.
+    pub fn poll_recv(&self, cx: &mut Context) -> Poll<Option<T>> {
+        match self.try_recv() {
+            Ok(v) => return Poll::Ready(Some(v)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        self.inner.flavor.receivers().register_waker(cx.waker().clone());
+
+        // Re-check after registering, in case a message arrived in the meantime.
+        match self.try_recv() {
+            Ok(v) => Poll::Ready(Some(v)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.inner.receiver_count.fetch_add(1, SeqCst);
+        Receiver { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        // The last receiver closes the channel so a subsequent `try_send` reports
+        // `Disconnected` instead of buffering into a channel nobody will ever drain.
+        if self.inner.receiver_count.fetch_sub(1, SeqCst) == 1 {
+            self.inner.flavor.close();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
+
+pub(crate) fn try_send<T>(sender: &Sender<T>, value: T) -> Result<(), TrySendError<T>> {
+    sender.inner.flavor.try_send(value)
+}
+
+pub(crate) fn try_recv<T>(receiver: &Receiver<T>) -> Result<T, TryRecvError> {
+    receiver.inner.flavor.try_recv()
+}
+
+pub(crate) fn receivers<T>(receiver: &Receiver<T>) -> &Monitor {
+    receiver.inner.flavor.receivers()
+}
+
+pub(crate) fn senders<T>(sender: &Sender<T>) -> Option<&Monitor> {
+    sender.inner.flavor.senders()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{bounded, unbounded};
+
+    unsafe fn noop_clone(_: *const ()) -> RawWaker {
+        noop_raw_waker()
+    }
+    unsafe fn noop(_: *const ()) {}
+
+    fn noop_raw_waker() -> RawWaker {
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(noop_clone, noop, noop, noop);
+        RawWaker::new(::std::ptr::null(), &VTABLE)
+    }
+
+    fn noop_waker() -> Waker {
+        unsafe { Waker::from_raw(noop_raw_waker()) }
+    }
+
+    #[test]
+    fn poll_recv_is_ready_once_a_message_is_sent() {
+        let (tx, rx) = unbounded::<i32>();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Pending);
+
+        tx.send(7).unwrap();
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Ready(Some(7)));
+    }
+
+    #[test]
+    fn poll_recv_is_ready_with_none_once_disconnected() {
+        let (tx, rx) = unbounded::<i32>();
+        drop(tx);
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Ready(None));
+    }
+
+    #[test]
+    fn poll_send_is_pending_while_a_bounded_channel_is_full() {
+        let (tx, _rx) = bounded::<i32>(1);
+        tx.try_send(1).unwrap();
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(tx.poll_send(&mut cx), Poll::Pending);
+    }
+
+    #[test]
+    fn poll_send_is_ready_for_an_unbounded_channel() {
+        let (tx, _rx) = unbounded::<i32>();
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(tx.poll_send(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn poll_send_is_pending_on_a_rendezvous_channel_with_no_waiting_receiver() {
+        let (tx, _rx) = bounded::<i32>(0);
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(tx.poll_send(&mut cx), Poll::Pending);
+    }
+
+    #[test]
+    fn poll_send_is_ready_on_a_rendezvous_channel_once_a_receiver_is_waiting() {
+        let (tx, rx) = bounded::<i32>(0);
+
+        let handle = thread::spawn(move || rx.recv());
+
+        // Give the receiver a chance to park before polling.
+        thread::sleep(Duration::from_millis(50));
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(tx.poll_send(&mut cx), Poll::Ready(()));
+
+        tx.send(1).unwrap();
+        assert_eq!(handle.join().unwrap(), Ok(1));
+    }
+
+    #[test]
+    fn channel_stays_open_while_any_sender_clone_remains() {
+        let (tx, rx) = unbounded::<i32>();
+        let tx2 = tx.clone();
+
+        drop(tx);
+        assert!(!rx.is_closed());
+
+        drop(tx2);
+        assert!(rx.is_closed());
+    }
+
+    #[test]
+    fn channel_stays_open_while_any_receiver_clone_remains() {
+        let (tx, rx) = unbounded::<i32>();
+        let rx2 = rx.clone();
+
+        drop(rx);
+        assert!(!tx.is_closed());
+
+        drop(rx2);
+        assert!(tx.is_closed());
+    }
+
+    #[test]
+    fn dropping_the_last_sender_makes_recv_report_disconnected() {
+        let (tx, rx) = unbounded::<i32>();
+        drop(tx);
+        assert_eq!(rx.recv(), Err(::err::RecvError));
+    }
+
+    #[test]
+    fn poll_recv_wakes_the_registered_waker_once_a_message_arrives() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+
+        unsafe fn clone(data: *const ()) -> RawWaker {
+            let flag = Arc::from_raw(data as *const AtomicBool);
+            let cloned = flag.clone();
+            ::std::mem::forget(flag);
+            RawWaker::new(Arc::into_raw(cloned) as *const (), &VTABLE)
+        }
+        unsafe fn wake(data: *const ()) {
+            let flag = Arc::from_raw(data as *const AtomicBool);
+            flag.store(true, SeqCst);
+        }
+        unsafe fn wake_by_ref(data: *const ()) {
+            let flag = Arc::from_raw(data as *const AtomicBool);
+            flag.store(true, SeqCst);
+            ::std::mem::forget(flag);
+        }
+        unsafe fn drop_flag(data: *const ()) {
+            drop(Arc::from_raw(data as *const AtomicBool));
+        }
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake_by_ref, drop_flag);
+
+        let (tx, rx) = unbounded::<i32>();
+        let flag = Arc::new(AtomicBool::new(false));
+        let raw = RawWaker::new(Arc::into_raw(flag.clone()) as *const (), &VTABLE);
+        let waker = unsafe { Waker::from_raw(raw) };
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Pending);
+        assert!(!flag.load(SeqCst));
+
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.send(1).unwrap();
+        });
+        handle.join().unwrap();
+
+        assert!(flag.load(SeqCst));
+    }
+}
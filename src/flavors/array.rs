@@ -0,0 +1,303 @@
+//! A bounded channel backed by a fixed-capacity array, as in the two-lock-queue and std-mpmc
+//! designs: each slot carries a stamp so producers and consumers can tell whether it is empty,
+//! full, or awaiting the other side without taking a global lock on every operation.
+
+use std::cell::UnsafeCell;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::atomic::Ordering::SeqCst;
+use std::time::Instant;
+
+use CaseId;
+use actor;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use monitor::Monitor;
+
+struct Slot<T> {
+    /// Twice the position this slot is waiting for: `2 * pos` while empty and ready for the
+    /// producer at `pos`, `2 * pos + 1` once that producer has written its value.
+    stamp: AtomicUsize,
+    value: UnsafeCell<T>,
+}
+
+#[repr(C)]
+pub(crate) struct Channel<T> {
+    buffer: Box<[Slot<T>]>,
+    cap: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    closed: AtomicBool,
+    senders: Monitor,
+    receivers: Monitor,
+}
+
+unsafe impl<T: Send> Send for Channel<T> {}
+unsafe impl<T: Send> Sync for Channel<T> {}
+
+impl<T> Channel<T> {
+    pub fn with_capacity(cap: usize) -> Self {
+        assert!(cap > 0, "capacity must be positive");
+
+        let buffer = (0..cap)
+            .map(|i| Slot {
+                stamp: AtomicUsize::new(2 * i),
+                value: UnsafeCell::new(unsafe { mem::uninitialized() }),
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+
+        Channel {
+            buffer,
+            cap,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            senders: Monitor::new(),
+            receivers: Monitor::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.load(SeqCst);
+            let head = self.head.load(SeqCst);
+
+            if self.tail.load(SeqCst) == tail {
+                return tail.wrapping_sub(head);
+            }
+        }
+    }
+
+    /// Claims the next slot for a producer, retrying as long as losing the CAS only means
+    /// ordinary contention with another producer racing for the same slot. Hands `value` back on
+    /// genuine failure so the caller can still report it in a `TrySendError::Full`.
+    fn push(&self, value: T) -> Result<(), T> {
+        let mut tail = self.tail.load(SeqCst);
+
+        loop {
+            let slot = &self.buffer[tail % self.cap];
+            let stamp = slot.stamp.load(SeqCst);
+
+            if stamp == 2 * tail {
+                match self.tail.compare_exchange(tail, tail + 1, SeqCst, SeqCst) {
+                    Ok(_) => {
+                        unsafe { *slot.value.get() = value };
+                        slot.stamp.store(2 * tail + 1, SeqCst);
+                        return Ok(());
+                    }
+                    Err(cur) => tail = cur,
+                }
+            } else if stamp < 2 * tail {
+                // The slot still holds an unconsumed value from a previous lap: genuinely full.
+                return Err(value);
+            } else {
+                // Another producer has already claimed this slot; reload and retry.
+                tail = self.tail.load(SeqCst);
+            }
+        }
+    }
+
+    /// Claims the next slot for a consumer, retrying as long as losing the CAS only means
+    /// ordinary contention with another consumer racing for the same slot.
+    fn pop(&self) -> Option<T> {
+        let mut head = self.head.load(SeqCst);
+
+        loop {
+            let slot = &self.buffer[head % self.cap];
+            let stamp = slot.stamp.load(SeqCst);
+
+            if stamp == 2 * head + 1 {
+                match self.head.compare_exchange(head, head + 1, SeqCst, SeqCst) {
+                    Ok(_) => {
+                        let value = unsafe { ptr::read(slot.value.get()) };
+                        slot.stamp.store(2 * (head + self.cap), SeqCst);
+                        return Some(value);
+                    }
+                    Err(cur) => head = cur,
+                }
+            } else if stamp < 2 * head + 1 {
+                // The slot hasn't been filled yet for this generation: genuinely empty.
+                return None;
+            } else {
+                // Another consumer has already claimed this slot; reload and retry.
+                head = self.head.load(SeqCst);
+            }
+        }
+    }
+
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        if self.closed.load(SeqCst) {
+            return Err(TrySendError::Disconnected(value));
+        }
+        match self.push(value) {
+            Ok(()) => {
+                self.receivers.notify_one();
+                Ok(())
+            }
+            Err(value) => if self.closed.load(SeqCst) {
+                Err(TrySendError::Disconnected(value))
+            } else {
+                Err(TrySendError::Full(value))
+            },
+        }
+    }
+
+    pub fn send(&self, mut value: T, deadline: Option<Instant>, case_id: CaseId) -> Result<(), SendTimeoutError<T>> {
+        loop {
+            match self.try_send(value) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(v)) => return Err(SendTimeoutError::Disconnected(v)),
+                Err(TrySendError::Full(v)) => value = v,
+            }
+
+            actor::current_reset();
+            self.senders.register(case_id);
+            let timed_out =
+                !self.is_closed() && self.len() == self.cap && !actor::current_wait_until(deadline);
+            self.senders.unregister(case_id);
+
+            if timed_out {
+                return Err(SendTimeoutError::Timeout(value));
+            }
+        }
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let closed = self.closed.load(SeqCst);
+        match self.pop() {
+            Some(v) => {
+                self.senders.notify_one();
+                Ok(v)
+            }
+            None => if closed {
+                Err(TryRecvError::Disconnected)
+            } else {
+                Err(TryRecvError::Empty)
+            },
+        }
+    }
+
+    pub fn recv_until(&self, deadline: Option<Instant>, case_id: CaseId) -> Result<T, RecvTimeoutError> {
+        loop {
+            match self.try_recv() {
+                Ok(v) => return Ok(v),
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+            }
+
+            actor::current_reset();
+            self.receivers.register(case_id);
+            let timed_out =
+                !self.is_closed() && self.len() == 0 && !actor::current_wait_until(deadline);
+            self.receivers.unregister(case_id);
+
+            if timed_out {
+                return Err(RecvTimeoutError::Timeout);
+            }
+        }
+    }
+
+    pub fn close(&self) -> bool {
+        if self.closed.swap(true, SeqCst) {
+            false
+        } else {
+            self.senders.abort_all();
+            self.receivers.abort_all();
+            true
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(SeqCst)
+    }
+
+    pub fn senders(&self) -> &Monitor {
+        &self.senders
+    }
+
+    pub fn receivers(&self) -> &Monitor {
+        &self.receivers
+    }
+}
+
+impl<T> Drop for Channel<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::Channel;
+    use err::{TryRecvError, TrySendError};
+
+    #[test]
+    fn try_send_fills_then_reports_full() {
+        let chan = Channel::with_capacity(2);
+        assert_eq!(chan.try_send(1), Ok(()));
+        assert_eq!(chan.try_send(2), Ok(()));
+        assert_eq!(chan.try_send(3), Err(TrySendError::Full(3)));
+    }
+
+    #[test]
+    fn try_recv_drains_then_reports_empty() {
+        let chan = Channel::with_capacity(2);
+        chan.try_send(1).unwrap();
+        assert_eq!(chan.try_recv(), Ok(1));
+        assert_eq!(chan.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn wraps_around_the_ring_buffer() {
+        let chan = Channel::with_capacity(2);
+        for i in 0..10 {
+            chan.try_send(i).unwrap();
+            assert_eq!(chan.try_recv(), Ok(i));
+        }
+    }
+
+    #[test]
+    fn closed_channel_reports_disconnected() {
+        let chan = Channel::with_capacity(2);
+        chan.try_send(1).unwrap();
+        chan.close();
+        assert_eq!(chan.try_send(2), Err(TrySendError::Disconnected(2)));
+        // Buffered messages still drain before `Disconnected` shows up.
+        assert_eq!(chan.try_recv(), Ok(1));
+        assert_eq!(chan.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn concurrent_producers_never_report_spurious_full() {
+        // Regression test: a single failed CAS on `tail` used to be treated as "full" even
+        // though it's ordinary contention between producers racing for the same slot.
+        const THREADS: usize = 16;
+        const PER_THREAD: usize = 2_000;
+
+        let chan = Arc::new(Channel::with_capacity(THREADS * PER_THREAD * 2));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let chan = chan.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_THREAD {
+                        chan.try_send(i).expect("channel has ample capacity");
+                    }
+                })
+            })
+            .collect();
+
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        assert_eq!(chan.len(), THREADS * PER_THREAD);
+    }
+}
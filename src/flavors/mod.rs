@@ -0,0 +1,5 @@
+//! Implementations of the channel, each built around a different backing structure.
+
+pub(crate) mod array;
+pub(crate) mod list;
+pub(crate) mod zero;
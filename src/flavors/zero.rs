@@ -0,0 +1,345 @@
+//! A zero-capacity (rendezvous) channel where a `send` only completes once a `recv` is there to
+//! receive it, and vice versa. Modeled on std-mpmc's `zero.rs`: the side that arrives first
+//! publishes a packet and parks; the side that arrives second hands the value straight through
+//! the packet and wakes the parked party.
+
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::SeqCst;
+use std::time::Instant;
+
+use CaseId;
+use actor;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use monitor::Monitor;
+
+/// A one-shot slot used to hand a single value directly from a sender to a receiver.
+struct Packet<T> {
+    slot: Mutex<Option<T>>,
+}
+
+impl<T> Packet<T> {
+    fn empty() -> Self {
+        Packet { slot: Mutex::new(None) }
+    }
+
+    fn filled(value: T) -> Self {
+        Packet { slot: Mutex::new(Some(value)) }
+    }
+
+    fn put(&self, value: T) {
+        *self.slot.lock().unwrap() = Some(value);
+    }
+
+    fn take(&self) -> Option<T> {
+        self.slot.lock().unwrap().take()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.slot.lock().unwrap().is_none()
+    }
+}
+
+struct Waiting<T> {
+    case_id: CaseId,
+    packet: *const Packet<T>,
+}
+
+unsafe impl<T: Send> Send for Waiting<T> {}
+
+pub(crate) struct Channel<T> {
+    senders_waiting: Mutex<Vec<Waiting<T>>>,
+    receivers_waiting: Mutex<Vec<Waiting<T>>>,
+    senders: Monitor,
+    receivers: Monitor,
+    closed: AtomicBool,
+}
+
+unsafe impl<T: Send> Send for Channel<T> {}
+unsafe impl<T: Send> Sync for Channel<T> {}
+
+impl<T> Channel<T> {
+    pub fn new() -> Self {
+        Channel {
+            senders_waiting: Mutex::new(Vec::new()),
+            receivers_waiting: Mutex::new(Vec::new()),
+            senders: Monitor::new(),
+            receivers: Monitor::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        if self.closed.load(SeqCst) {
+            return Err(TrySendError::Disconnected(value));
+        }
+
+        let mut receivers_waiting = self.receivers_waiting.lock().unwrap();
+        match receivers_waiting.pop() {
+            Some(waiting) => {
+                drop(receivers_waiting);
+                unsafe { (*waiting.packet).put(value) };
+                // Wake exactly the receiver whose packet we just filled: the waiting-list and
+                // the monitor's watcher list are separate, so a plain `notify_one` could instead
+                // wake an unrelated parked receiver and leave this one asleep forever.
+                self.receivers.notify(waiting.case_id);
+                Ok(())
+            }
+            None => Err(TrySendError::Full(value)),
+        }
+    }
+
+    pub fn send(
+        &self,
+        value: T,
+        deadline: Option<Instant>,
+        case_id: CaseId,
+    ) -> Result<(), SendTimeoutError<T>> {
+        let value = match self.try_send(value) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Disconnected(v)) => return Err(SendTimeoutError::Disconnected(v)),
+            Err(TrySendError::Full(v)) => v,
+        };
+
+        let packet = Packet::filled(value);
+        self.senders_waiting.lock().unwrap().push(Waiting {
+            case_id,
+            packet: &packet,
+        });
+
+        actor::current_reset();
+        self.senders.register(case_id);
+        self.receivers.notify_one();
+
+        loop {
+            if packet.is_empty() {
+                // A receiver took the value directly out of our packet.
+                self.senders.unregister(case_id);
+                return Ok(());
+            }
+
+            if self.closed.load(SeqCst) {
+                self.senders.unregister(case_id);
+                self.senders_waiting.lock().unwrap().retain(|w| w.case_id != case_id);
+                // A receiver may have taken the value out from under us between the
+                // `is_empty()` check above and here; if so, the send already succeeded.
+                if let Some(value) = packet.take() {
+                    return Err(SendTimeoutError::Disconnected(value));
+                }
+                return Ok(());
+            }
+
+            if !actor::current_wait_until(deadline) {
+                self.senders.unregister(case_id);
+                self.senders_waiting.lock().unwrap().retain(|w| w.case_id != case_id);
+                if let Some(value) = packet.take() {
+                    return Err(SendTimeoutError::Timeout(value));
+                }
+                return Ok(());
+            }
+            actor::current_reset();
+        }
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut senders_waiting = self.senders_waiting.lock().unwrap();
+        match senders_waiting.pop() {
+            Some(waiting) => {
+                drop(senders_waiting);
+                let value = unsafe { (*waiting.packet).take() };
+                // As in `try_send`, wake exactly the sender whose packet we just drained.
+                self.senders.notify(waiting.case_id);
+                match value {
+                    Some(v) => Ok(v),
+                    None => Err(TryRecvError::Empty),
+                }
+            }
+            None => if self.closed.load(SeqCst) {
+                Err(TryRecvError::Disconnected)
+            } else {
+                Err(TryRecvError::Empty)
+            },
+        }
+    }
+
+    pub fn recv_until(
+        &self,
+        deadline: Option<Instant>,
+        case_id: CaseId,
+    ) -> Result<T, RecvTimeoutError> {
+        match self.try_recv() {
+            Ok(v) => return Ok(v),
+            Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        let packet = Packet::empty();
+        self.receivers_waiting.lock().unwrap().push(Waiting {
+            case_id,
+            packet: &packet,
+        });
+
+        actor::current_reset();
+        self.receivers.register(case_id);
+        self.senders.notify_one();
+
+        loop {
+            if let Some(value) = packet.take() {
+                self.receivers.unregister(case_id);
+                return Ok(value);
+            }
+
+            if self.closed.load(SeqCst) {
+                self.receivers.unregister(case_id);
+                self.receivers_waiting.lock().unwrap().retain(|w| w.case_id != case_id);
+                if let Some(value) = packet.take() {
+                    return Ok(value);
+                }
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            if !actor::current_wait_until(deadline) {
+                self.receivers.unregister(case_id);
+                self.receivers_waiting.lock().unwrap().retain(|w| w.case_id != case_id);
+                if let Some(value) = packet.take() {
+                    return Ok(value);
+                }
+                return Err(RecvTimeoutError::Timeout);
+            }
+            actor::current_reset();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        0
+    }
+
+    pub fn close(&self) -> bool {
+        if self.closed.swap(true, SeqCst) {
+            false
+        } else {
+            self.senders.abort_all();
+            self.receivers.abort_all();
+            true
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(SeqCst)
+    }
+
+    /// Returns `true` if a receiver is currently parked and ready to take a value directly, i.e.
+    /// a `try_send` would succeed right now.
+    pub fn has_waiting_receiver(&self) -> bool {
+        !self.receivers_waiting.lock().unwrap().is_empty()
+    }
+
+    pub fn senders(&self) -> &Monitor {
+        &self.senders
+    }
+
+    pub fn receivers(&self) -> &Monitor {
+        &self.receivers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::Channel;
+    use CaseId;
+    use err::{TryRecvError, TrySendError};
+
+    #[test]
+    fn try_send_without_a_receiver_is_full() {
+        let chan: Channel<i32> = Channel::new();
+        assert_eq!(chan.try_send(1), Err(TrySendError::Full(1)));
+    }
+
+    #[test]
+    fn try_recv_without_a_sender_is_empty() {
+        let chan: Channel<i32> = Channel::new();
+        assert_eq!(chan.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn blocking_recv_completes_once_a_try_send_arrives() {
+        let chan = Arc::new(Channel::new());
+        let receiver = chan.clone();
+
+        let handle = thread::spawn(move || receiver.recv_until(None, CaseId::new()));
+
+        // Give the receiver a chance to park before the value shows up.
+        thread::sleep(Duration::from_millis(50));
+        chan.try_send(7).unwrap();
+
+        assert_eq!(handle.join().unwrap(), Ok(7));
+    }
+
+    #[test]
+    fn blocking_send_completes_once_a_try_recv_arrives() {
+        let chan = Arc::new(Channel::new());
+        let sender = chan.clone();
+
+        let handle = thread::spawn(move || sender.send(7, None, CaseId::new()));
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(chan.try_recv(), Ok(7));
+
+        assert_eq!(handle.join().unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn many_concurrent_senders_and_receivers_all_rendezvous() {
+        // Regression test: notifying an arbitrary parked actor instead of the one whose packet
+        // was actually matched up used to strand the real counterpart forever.
+        const PAIRS: usize = 8;
+
+        let chan = Arc::new(Channel::new());
+
+        let senders: Vec<_> = (0..PAIRS)
+            .map(|i| {
+                let chan = chan.clone();
+                thread::spawn(move || chan.send(i, None, CaseId::new()).unwrap())
+            })
+            .collect();
+
+        let receivers: Vec<_> = (0..PAIRS)
+            .map(|_| {
+                let chan = chan.clone();
+                thread::spawn(move || chan.recv_until(None, CaseId::new()).unwrap())
+            })
+            .collect();
+
+        for h in senders {
+            h.join().unwrap();
+        }
+        let mut received: Vec<_> = receivers.into_iter().map(|h| h.join().unwrap()).collect();
+        received.sort();
+        assert_eq!(received, (0..PAIRS).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn send_with_deadline_never_panics_when_a_try_recv_drains_it_first() {
+        // Regression test: a `try_recv` can pop this sender's `Waiting` entry and drain its
+        // packet in the window between the `is_empty()` check and the timeout/closed branch,
+        // which used to `unwrap()` the now-empty packet and panic instead of reporting success.
+        const ROUNDS: usize = 2_000;
+
+        for i in 0..ROUNDS {
+            let chan = Arc::new(Channel::new());
+            let deadline = Instant::now() + Duration::from_millis(1);
+
+            let sender = chan.clone();
+            let handle = thread::spawn(move || sender.send(i, Some(deadline), CaseId::new()));
+
+            // Race a `try_recv` against the sender's own timeout.
+            let _ = chan.try_recv();
+
+            handle.join().unwrap();
+        }
+    }
+}
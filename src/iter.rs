@@ -0,0 +1,126 @@
+//! Iterator adapters over a [`Receiver`], mirroring `std::sync::mpsc`'s `Iter`/`TryIter`/`IntoIter`.
+
+use channel::Receiver;
+
+/// A blocking iterator over messages received from a channel.
+///
+/// Each call to `next` blocks until a message arrives, returning `None` once the channel is
+/// disconnected and drained.
+pub struct Iter<'a, T: 'a> {
+    rx: &'a Receiver<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+/// A non-blocking iterator over messages received from a channel.
+///
+/// Stops as soon as a `recv` would block, even if the channel could later produce more messages.
+pub struct TryIter<'a, T: 'a> {
+    rx: &'a Receiver<T>,
+}
+
+impl<'a, T> Iterator for TryIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// An owning, blocking iterator over messages received from a channel.
+pub struct IntoIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns a blocking iterator over messages received from this channel.
+    pub fn iter(&self) -> Iter<T> {
+        Iter { rx: self }
+    }
+
+    /// Returns a non-blocking iterator over messages received from this channel.
+    pub fn try_iter(&self) -> TryIter<T> {
+        TryIter { rx: self }
+    }
+}
+
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { rx: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use channel::unbounded;
+
+    #[test]
+    fn iter_stops_once_the_channel_is_disconnected_and_drained() {
+        let (tx, rx) = unbounded::<i32>();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        drop(tx);
+
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn try_iter_stops_as_soon_as_it_would_block() {
+        let (tx, rx) = unbounded::<i32>();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn into_iter_consumes_the_receiver() {
+        let (tx, rx) = unbounded::<i32>();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        drop(tx);
+
+        assert_eq!(rx.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn for_loop_uses_the_borrowing_iterator() {
+        let (tx, rx) = unbounded::<i32>();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        drop(tx);
+
+        let mut sum = 0;
+        for v in &rx {
+            sum += v;
+        }
+        assert_eq!(sum, 3);
+    }
+}
@@ -0,0 +1,45 @@
+//! Multi-producer multi-consumer channels for message passing.
+
+extern crate crossbeam_epoch as epoch;
+extern crate futures;
+extern crate rand;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+
+mod actor;
+mod channel;
+mod err;
+mod flavors;
+mod iter;
+mod monitor;
+pub mod select;
+mod stream;
+
+pub use channel::{bounded, unbounded, Receiver, Sender};
+pub use iter::{IntoIter, Iter, TryIter};
+pub use stream::Recv;
+pub use err::{
+    RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError,
+};
+pub use select::Select;
+
+/// Identifies a single send or receive operation registered with a channel's `Monitor`.
+///
+/// Every case competing in a `select!` gets its own id so that a wakeup can be traced back to the
+/// operation that caused it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct CaseId(usize);
+
+impl CaseId {
+    /// The sentinel id that names no operation, e.g. the case recorded by an actor that timed out.
+    pub(crate) fn none() -> Self {
+        CaseId(0)
+    }
+
+    /// Generates a fresh, process-wide unique case id.
+    pub(crate) fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(1);
+        CaseId(NEXT.fetch_add(1, SeqCst))
+    }
+}
@@ -0,0 +1,90 @@
+//! A wait queue that channel flavors use to park and wake blocked operations.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Waker;
+
+use CaseId;
+use actor::{self, Actor};
+
+/// A set of actors and async tasks parked on operations registered under their `CaseId`.
+pub struct Monitor {
+    watchers: Mutex<Vec<(CaseId, Arc<Actor>)>>,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Monitor {
+    /// Creates a new, empty monitor.
+    pub fn new() -> Self {
+        Monitor {
+            watchers: Mutex::new(Vec::new()),
+            wakers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers the current thread's actor as waiting under `case_id`.
+    pub fn register(&self, case_id: CaseId) {
+        self.watchers.lock().unwrap().push((case_id, actor::current()));
+    }
+
+    /// Unregisters the operation previously registered under `case_id`.
+    pub fn unregister(&self, case_id: CaseId) {
+        let mut watchers = self.watchers.lock().unwrap();
+        if let Some(i) = watchers.iter().position(|&(id, _)| id == case_id) {
+            watchers.remove(i);
+        }
+    }
+
+    /// Registers a task's `Waker` to be woken by a later `notify_one` that finds no actor waiting,
+    /// or by `abort_all`.
+    pub fn register_waker(&self, waker: Waker) {
+        let mut wakers = self.wakers.lock().unwrap();
+        // Avoid letting a task that polls repeatedly without progress pile up duplicates.
+        wakers.retain(|w| !w.will_wake(&waker));
+        wakers.push(waker);
+    }
+
+    /// Wakes up one waiting actor or task, if any.
+    pub fn notify_one(&self) {
+        let mut watchers = self.watchers.lock().unwrap();
+        if let Some((case_id, actor)) = watchers.pop() {
+            actor.wake(case_id);
+            return;
+        }
+        drop(watchers);
+
+        let mut wakers = self.wakers.lock().unwrap();
+        if let Some(waker) = wakers.pop() {
+            waker.wake();
+        }
+    }
+
+    /// Wakes up the specific actor registered under `case_id`, if it is still waiting.
+    ///
+    /// Unlike [`notify_one`](Monitor::notify_one), which wakes whichever actor happens to be on
+    /// top of the watcher list, this targets one precise operation. Use it when a counterpart has
+    /// already been matched up with a particular waiter by identity (e.g. a rendezvous handoff)
+    /// and an arbitrary wakeup could miss it.
+    pub fn notify(&self, case_id: CaseId) {
+        let mut watchers = self.watchers.lock().unwrap();
+        if let Some(i) = watchers.iter().position(|&(id, _)| id == case_id) {
+            let (case_id, actor) = watchers.remove(i);
+            drop(watchers);
+            actor.wake(case_id);
+        }
+    }
+
+    /// Wakes up every waiting actor and task. Used when the channel is closed.
+    pub fn abort_all(&self) {
+        let mut watchers = self.watchers.lock().unwrap();
+        for (case_id, actor) in watchers.drain(..) {
+            actor.wake(case_id);
+        }
+        drop(watchers);
+
+        let mut wakers = self.wakers.lock().unwrap();
+        for waker in wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
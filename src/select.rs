@@ -0,0 +1,275 @@
+//! Waiting on multiple channel operations at once.
+//!
+//! [`Select`] registers a number of receive and send operations and blocks until exactly one of
+//! them can proceed, picking among the ready ones at random so that no single case starves the
+//! others under contention. The [`select!`] macro is sugar on top of it.
+
+use std::time::{Duration, Instant};
+
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+
+use CaseId;
+use actor;
+use channel::{self, Receiver, Sender};
+use err::{RecvError, TryRecvError, TrySendError};
+use monitor::Monitor;
+
+struct Case<'a> {
+    case_id: CaseId,
+    monitor: Option<&'a Monitor>,
+    /// Attempts the operation once. Returns `true` if it fired (and has already run the user's
+    /// callback), `false` if it would block.
+    try_fire: Box<FnMut() -> bool + 'a>,
+}
+
+/// A builder for waiting on several channel operations at once.
+pub struct Select<'a> {
+    cases: Vec<Case<'a>>,
+    default: Option<Box<FnMut() + 'a>>,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty `Select`.
+    pub fn new() -> Self {
+        Select {
+            cases: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Registers a receive operation on `rx`, running `f` with the result once it fires.
+    pub fn recv<T, F>(mut self, rx: &'a Receiver<T>, mut f: F) -> Self
+    where
+        F: FnMut(Result<T, RecvError>) + 'a,
+    {
+        let case_id = CaseId::new();
+        self.cases.push(Case {
+            case_id,
+            monitor: Some(channel::receivers(rx)),
+            try_fire: Box::new(move || match channel::try_recv(rx) {
+                Ok(v) => {
+                    f(Ok(v));
+                    true
+                }
+                Err(TryRecvError::Disconnected) => {
+                    f(Err(RecvError));
+                    true
+                }
+                Err(TryRecvError::Empty) => false,
+            }),
+        });
+        self
+    }
+
+    /// Registers a send operation of `msg` on `tx`, running `f` once it fires.
+    pub fn send<T, F>(mut self, tx: &'a Sender<T>, msg: T, mut f: F) -> Self
+    where
+        F: FnMut() + 'a,
+    {
+        let case_id = CaseId::new();
+        let mut msg = Some(msg);
+        self.cases.push(Case {
+            case_id,
+            monitor: channel::senders(tx),
+            try_fire: Box::new(move || match channel::try_send(tx, msg.take().unwrap()) {
+                Ok(()) => {
+                    f();
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    f();
+                    true
+                }
+                Err(TrySendError::Full(v)) => {
+                    msg = Some(v);
+                    false
+                }
+            }),
+        });
+        self
+    }
+
+    /// Registers a default case that fires immediately if no other case is ready.
+    pub fn default<F>(mut self, f: F) -> Self
+    where
+        F: FnMut() + 'a,
+    {
+        self.default = Some(Box::new(f));
+        self
+    }
+
+    /// Waits until one of the registered cases fires, running its callback.
+    pub fn wait(self) {
+        self.wait_until(None);
+    }
+
+    /// Like [`wait`](Select::wait), but gives up after `timeout` elapses, returning `false` if no
+    /// case fired. A default case, if registered, fires immediately when nothing is ready, so the
+    /// wait then never times out.
+    pub fn wait_timeout(self, timeout: Duration) -> bool {
+        self.wait_until(Some(Instant::now() + timeout))
+    }
+
+    fn wait_until(mut self, deadline: Option<Instant>) -> bool {
+        loop {
+            let mut order: Vec<usize> = (0..self.cases.len()).collect();
+            order.shuffle(&mut thread_rng());
+
+            for i in order {
+                if (self.cases[i].try_fire)() {
+                    return true;
+                }
+            }
+
+            if let Some(ref mut default) = self.default {
+                default();
+                return true;
+            }
+
+            actor::current_reset();
+            for case in &self.cases {
+                if let Some(monitor) = case.monitor {
+                    monitor.register(case.case_id);
+                }
+            }
+            let woken = actor::current_wait_until(deadline);
+            for case in &self.cases {
+                if let Some(monitor) = case.monitor {
+                    monitor.unregister(case.case_id);
+                }
+            }
+
+            if !woken {
+                return false;
+            }
+
+            // Try the specific case that woke us up first, since it's the one a monitor actually
+            // matched us up with; fall through to the full shuffle-and-retry above if it turns
+            // out another operation won the race for it in the meantime.
+            let selected = actor::current_selected();
+            if let Some(i) = self.cases.iter().position(|case| case.case_id == selected) {
+                if (self.cases[i].try_fire)() {
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+/// Blocks on several channel operations at once, running the body of whichever fires first.
+///
+/// ```ignore
+/// select! {
+///     recv(rx) -> msg => println!("received {:?}", msg),
+///     send(tx, 7) => println!("sent"),
+///     default => println!("no channel was ready"),
+/// }
+/// ```
+#[macro_export]
+macro_rules! select {
+    (@build $select:expr;) => {
+        $select.wait()
+    };
+    (@build $select:expr; recv($rx:expr) -> $res:pat => $body:expr, $($tail:tt)*) => {
+        select!(@build $select.recv(&$rx, |$res| $body); $($tail)*)
+    };
+    (@build $select:expr; send($tx:expr, $msg:expr) => $body:expr, $($tail:tt)*) => {
+        select!(@build $select.send(&$tx, $msg, || $body); $($tail)*)
+    };
+    (@build $select:expr; default => $body:expr $(,)*) => {
+        $select.default(|| $body).wait()
+    };
+    ($($tail:tt)*) => {
+        select!(@build $crate::select::Select::new(); $($tail)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::thread;
+    use std::time::Duration;
+
+    use channel::{self, bounded, unbounded};
+    use select::Select;
+
+    #[test]
+    fn recv_fires_for_the_ready_channel() {
+        let (tx1, rx1) = unbounded::<i32>();
+        let (_tx2, rx2) = unbounded::<i32>();
+        tx1.send(1).unwrap();
+
+        let got = Cell::new(None);
+        Select::new()
+            .recv(&rx1, |v| got.set(Some(v.unwrap())))
+            .recv(&rx2, |_| panic!("rx2 has nothing to offer"))
+            .wait();
+
+        assert_eq!(got.into_inner(), Some(1));
+    }
+
+    #[test]
+    fn default_fires_when_nothing_is_ready() {
+        let (_tx, rx) = unbounded::<i32>();
+
+        let fired_default = Cell::new(false);
+        Select::new()
+            .recv(&rx, |_| panic!("channel is empty"))
+            .default(|| fired_default.set(true))
+            .wait();
+
+        assert!(fired_default.into_inner());
+    }
+
+    #[test]
+    fn wait_timeout_gives_up_once_the_deadline_passes() {
+        let (_tx, rx) = unbounded::<i32>();
+
+        let fired = Select::new()
+            .recv(&rx, |_| panic!("channel is empty"))
+            .wait_timeout(Duration::from_millis(20));
+
+        assert!(!fired);
+    }
+
+    #[test]
+    fn select_macro_picks_the_ready_recv_case() {
+        let (tx, rx) = unbounded::<i32>();
+        tx.send(42).unwrap();
+
+        let got = Cell::new(0);
+        select! {
+            recv(rx) -> msg => got.set(msg.unwrap()),
+            default => panic!("rx had a message queued"),
+        }
+
+        assert_eq!(got.into_inner(), 42);
+    }
+
+    #[test]
+    fn select_wakes_up_once_a_blocked_send_completes() {
+        // Exercises the parking path: no case is ready up front, so `wait()` has to register on
+        // each channel's Monitor and block until a send from another thread wakes it.
+        let (tx, rx) = bounded::<i32>(0);
+
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.send(9).unwrap();
+        });
+
+        let got = Cell::new(None);
+        Select::new()
+            .recv(&rx, |v| got.set(Some(v.unwrap())))
+            .wait();
+
+        handle.join().unwrap();
+        assert_eq!(got.into_inner(), Some(9));
+    }
+
+    #[test]
+    fn senders_helper_is_none_for_unbounded_channels() {
+        let (tx, _rx) = unbounded::<i32>();
+        assert!(channel::senders(&tx).is_none());
+    }
+}
@@ -0,0 +1,87 @@
+//! Async integration: a `Future` for a single receive, and a `Stream` over all of them.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use channel::Receiver;
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        self.poll_recv(cx)
+    }
+}
+
+/// A future that resolves to the next message received on a channel, or `None` once it is
+/// disconnected and drained. Returned by [`Receiver::recv_async`].
+pub struct Recv<'a, T: 'a> {
+    rx: &'a Receiver<T>,
+}
+
+impl<'a, T> Future for Recv<'a, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns a future that resolves to the next message received on this channel.
+    pub fn recv_async(&self) -> Recv<T> {
+        Recv { rx: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    use futures::Stream;
+
+    use channel::unbounded;
+
+    unsafe fn noop_clone(_: *const ()) -> RawWaker {
+        noop_raw_waker()
+    }
+    unsafe fn noop(_: *const ()) {}
+
+    fn noop_raw_waker() -> RawWaker {
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(noop_clone, noop, noop, noop);
+        RawWaker::new(::std::ptr::null(), &VTABLE)
+    }
+
+    fn noop_waker() -> Waker {
+        unsafe { Waker::from_raw(noop_raw_waker()) }
+    }
+
+    #[test]
+    fn poll_next_yields_messages_in_order() {
+        let (tx, mut rx) = unbounded::<i32>();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(Pin::new(&mut rx).poll_next(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(Pin::new(&mut rx).poll_next(&mut cx), Poll::Ready(Some(2)));
+    }
+
+    #[test]
+    fn recv_async_resolves_once_a_message_is_sent() {
+        let (tx, rx) = unbounded::<i32>();
+        tx.send(1).unwrap();
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(Pin::new(&mut rx.recv_async()).poll(&mut cx), Poll::Ready(Some(1)));
+    }
+}